@@ -0,0 +1,110 @@
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, DeriveInput, Ident, LitInt, Token};
+
+/// Derives `coerce::remote::net::StreamMessage` for any type that also
+/// implements `RemoteMessage` (i.e. `Serialize + DeserializeOwned + Send +
+/// 'static`), encoding through the same JSON encoder used for remote actor
+/// messages instead of requiring a hand-rolled `read_from_bytes`/
+/// `write_to_bytes` impl.
+///
+/// Add `#[stream_message(versioned)]` to prefix the encoded bytes with a
+/// format/version byte, so `read_from_bytes` can reject an encoding written
+/// by a different version of the type while a cluster is mid-rollout. Use
+/// `#[stream_message(versioned = 2)]` to pick the version explicitly when
+/// bumping it for a breaking wire-format change.
+#[proc_macro_derive(StreamMessage, attributes(stream_message))]
+pub fn derive_stream_message(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let remote_message_bound = quote! {
+        #ident #ty_generics: ::coerce::actor::message::encoding::json::RemoteMessage
+    };
+    let where_clause = match where_clause {
+        Some(where_clause) => quote! { #where_clause, #remote_message_bound },
+        None => quote! { where #remote_message_bound },
+    };
+    let versioned = input
+        .attrs
+        .iter()
+        .find(|attr| attr.path.is_ident("stream_message"))
+        .map(|attr| {
+            attr.parse_args::<StreamMessageArgs>()
+                .unwrap_or_else(|e| panic!("invalid #[stream_message(..)] attribute: {}", e))
+        });
+
+    let write_body = match &versioned {
+        Some(args) => {
+            let version = args.version_expr();
+            quote! { ::coerce::remote::net::write_versioned_envelope(#version, body) }
+        }
+        None => quote! { body },
+    };
+
+    let read_body = match &versioned {
+        Some(args) => {
+            let version = args.version_expr();
+            quote! {
+                let data = ::coerce::remote::net::read_versioned_envelope(#version, data)?;
+            }
+        }
+        None => quote! {},
+    };
+
+    let expanded = quote! {
+        impl #impl_generics ::coerce::remote::net::StreamMessage for #ident #ty_generics #where_clause {
+            fn read_from_bytes(data: ::std::vec::Vec<u8>) -> ::std::option::Option<Self> {
+                #read_body
+                ::serde_json::from_slice(&data).ok()
+            }
+
+            fn write_to_bytes(&self) -> ::std::option::Option<::std::vec::Vec<u8>> {
+                let body = ::serde_json::to_vec(self).ok()?;
+                ::std::option::Option::Some(#write_body)
+            }
+        }
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// Parsed contents of `#[stream_message(..)]`: either bare `versioned`, or
+/// `versioned = N` to pick the version explicitly.
+struct StreamMessageArgs {
+    version: Option<u8>,
+}
+
+impl StreamMessageArgs {
+    /// The version to embed, as an expression - either the literal version
+    /// given via `versioned = N`, or a reference to the crate's default so
+    /// bare `#[stream_message(versioned)]` stays in sync with it.
+    fn version_expr(&self) -> proc_macro2::TokenStream {
+        match self.version {
+            Some(version) => quote! { #version },
+            None => quote! { ::coerce::remote::net::DEFAULT_STREAM_MESSAGE_ENVELOPE_VERSION },
+        }
+    }
+}
+
+impl Parse for StreamMessageArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ident: Ident = input.parse()?;
+        if ident != "versioned" {
+            return Err(syn::Error::new(ident.span(), "expected `versioned`"));
+        }
+
+        let version = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            let lit: LitInt = input.parse()?;
+            Some(lit.base10_parse()?)
+        } else {
+            None
+        };
+
+        Ok(StreamMessageArgs { version })
+    }
+}
@@ -0,0 +1,40 @@
+use crate::actor::{Actor, ActorId, LocalActorRef};
+use crate::remote::stream::pubsub::Registry;
+use std::any::Any;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+/// Per-actor handle into the system the actor is running on. Carries the
+/// actor's own identity and a way to recover a typed reference to itself
+/// (e.g. to hand out to a pub/sub subscription).
+pub struct ActorContext {
+    id: ActorId,
+    self_ref: Box<dyn Any + Send + Sync>,
+    pub(crate) pubsub_registry: Arc<RwLock<Registry>>,
+}
+
+impl ActorContext {
+    pub(crate) fn new<A: Actor>(
+        id: ActorId,
+        self_ref: LocalActorRef<A>,
+        pubsub_registry: Arc<RwLock<Registry>>,
+    ) -> Self {
+        ActorContext {
+            id,
+            self_ref: Box::new(self_ref),
+            pubsub_registry,
+        }
+    }
+
+    pub fn id(&self) -> &ActorId {
+        &self.id
+    }
+
+    /// Recover a typed reference to the actor this context belongs to.
+    pub fn actor_ref<A: Actor>(&self) -> LocalActorRef<A> {
+        self.self_ref
+            .downcast_ref::<LocalActorRef<A>>()
+            .expect("ActorContext::actor_ref called with the wrong actor type")
+            .clone()
+    }
+}
@@ -0,0 +1,8 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Marker for messages that can be sent to and from a remote actor, encoded
+/// as JSON.
+pub trait RemoteMessage: Serialize + DeserializeOwned + Send + 'static {}
+
+impl<T: Serialize + DeserializeOwned + Send + 'static> RemoteMessage for T {}
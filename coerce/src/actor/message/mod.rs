@@ -0,0 +1,74 @@
+pub mod encoding;
+
+use crate::actor::context::ActorContext;
+use crate::actor::Actor;
+use async_trait::async_trait;
+
+/// Marker trait for actor messages.
+pub trait Message: Send + 'static {
+    type Result: Send;
+}
+
+/// Implemented by an actor for every message type it can receive.
+#[async_trait]
+pub trait Handler<M: Send + 'static>: Actor {
+    async fn handle(&mut self, message: M, ctx: &mut ActorContext);
+}
+
+/// Object-safe wrapper so an actor's mailbox can hold heterogeneous message
+/// types behind a single channel.
+#[async_trait]
+pub(crate) trait Envelope<A: Actor>: Send {
+    async fn handle(self: Box<Self>, actor: &mut A, ctx: &mut ActorContext);
+}
+
+pub(crate) struct MessageEnvelope<M> {
+    message: M,
+}
+
+impl<M> MessageEnvelope<M> {
+    pub fn new(message: M) -> Self {
+        MessageEnvelope { message }
+    }
+}
+
+#[async_trait]
+impl<A, M> Envelope<A> for MessageEnvelope<M>
+where
+    A: Handler<M>,
+    M: Send + 'static,
+{
+    async fn handle(self: Box<Self>, actor: &mut A, ctx: &mut ActorContext) {
+        actor.handle(self.message, ctx).await;
+    }
+}
+
+pub(crate) struct ExecEnvelope<A, F, R> {
+    f: Option<F>,
+    reply: Option<tokio::sync::oneshot::Sender<R>>,
+    _actor: std::marker::PhantomData<A>,
+}
+
+impl<A, F, R> ExecEnvelope<A, F, R> {
+    pub fn new(f: F, reply: tokio::sync::oneshot::Sender<R>) -> Self {
+        ExecEnvelope {
+            f: Some(f),
+            reply: Some(reply),
+            _actor: std::marker::PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<A, F, R> Envelope<A> for ExecEnvelope<A, F, R>
+where
+    A: Actor,
+    F: FnOnce(&A) -> R + Send + 'static,
+    R: Send + 'static,
+{
+    async fn handle(mut self: Box<Self>, actor: &mut A, _ctx: &mut ActorContext) {
+        if let (Some(f), Some(reply)) = (self.f.take(), self.reply.take()) {
+            let _ = reply.send(f(actor));
+        }
+    }
+}
@@ -0,0 +1,97 @@
+pub mod context;
+pub mod message;
+pub mod system;
+
+use crate::actor::context::ActorContext;
+use crate::actor::message::{Envelope, ExecEnvelope, Handler, MessageEnvelope};
+use async_trait::async_trait;
+use std::fmt;
+use std::sync::Arc;
+use tokio::sync::{mpsc, oneshot};
+
+/// Uniquely identifies an actor within an [`system::ActorSystem`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ActorId(Arc<str>);
+
+impl ActorId {
+    pub fn new(id: impl Into<String>) -> Self {
+        ActorId(Arc::from(id.into()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ActorId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActorRefErr {
+    ActorUnavailable,
+}
+
+/// Implemented by every actor. `started`/`stopped` are lifecycle hooks run
+/// once the actor's mailbox is ready and just before it is torn down.
+#[async_trait]
+pub trait Actor: Send + Sync + 'static {
+    async fn started(&mut self, _ctx: &mut ActorContext) {}
+
+    async fn stopped(&mut self, _ctx: &mut ActorContext) {}
+}
+
+/// A handle to a running actor's mailbox.
+pub struct LocalActorRef<A: Actor> {
+    pub(crate) id: ActorId,
+    pub(crate) sender: mpsc::UnboundedSender<Box<dyn Envelope<A>>>,
+}
+
+impl<A: Actor> Clone for LocalActorRef<A> {
+    fn clone(&self) -> Self {
+        LocalActorRef {
+            id: self.id.clone(),
+            sender: self.sender.clone(),
+        }
+    }
+}
+
+impl<A: Actor> LocalActorRef<A> {
+    pub fn id(&self) -> &ActorId {
+        &self.id
+    }
+
+    /// Fire-and-forget delivery of a message to the actor's mailbox.
+    pub fn notify<M>(&self, message: M) -> Result<(), ActorRefErr>
+    where
+        A: Handler<M>,
+        M: Send + 'static,
+    {
+        self.sender
+            .send(Box::new(MessageEnvelope::new(message)))
+            .map_err(|_| ActorRefErr::ActorUnavailable)
+    }
+
+    /// Run a closure against the actor's state and await the result.
+    pub async fn exec<F, R>(&self, f: F) -> Result<R, ActorRefErr>
+    where
+        F: FnOnce(&A) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send(Box::new(ExecEnvelope::new(f, tx)))
+            .map_err(|_| ActorRefErr::ActorUnavailable)?;
+        rx.await.map_err(|_| ActorRefErr::ActorUnavailable)
+    }
+}
+
+/// Starts `actor` on `system`, returning a reference to its mailbox.
+pub async fn new_actor<A: Actor>(
+    actor: A,
+    system: &system::ActorSystem,
+) -> Result<LocalActorRef<A>, ActorRefErr> {
+    system.new_anon_actor(actor).await
+}
@@ -0,0 +1,66 @@
+use crate::actor::context::ActorContext;
+use crate::actor::message::Envelope;
+use crate::actor::{Actor, ActorId, ActorRefErr, LocalActorRef};
+use crate::remote::stream::pubsub::Registry;
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
+use uuid::Uuid;
+
+/// Owns the actors running in-process, plus the state they share, such as
+/// the pub/sub subscriber registry.
+#[derive(Clone)]
+pub struct ActorSystem {
+    pubsub_registry: Arc<RwLock<Registry>>,
+}
+
+impl Default for ActorSystem {
+    fn default() -> Self {
+        ActorSystem {
+            pubsub_registry: Arc::new(RwLock::new(Registry::default())),
+        }
+    }
+}
+
+impl ActorSystem {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn pubsub_registry(&self) -> Arc<RwLock<Registry>> {
+        self.pubsub_registry.clone()
+    }
+
+    /// Starts `actor` with a freshly generated [`ActorId`].
+    pub async fn new_anon_actor<A: Actor>(&self, actor: A) -> Result<LocalActorRef<A>, ActorRefErr> {
+        self.new_actor(ActorId::new(Uuid::new_v4().to_string()), actor)
+            .await
+    }
+
+    /// Starts `actor`, returning only once `actor`'s `started` hook has run -
+    /// e.g. a `PubSub::subscribe` call made from `started` is guaranteed to be
+    /// in place before this returns, so a publish right after is never racing
+    /// the subscription.
+    pub async fn new_actor<A: Actor>(
+        &self,
+        id: ActorId,
+        mut actor: A,
+    ) -> Result<LocalActorRef<A>, ActorRefErr> {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<Box<dyn Envelope<A>>>();
+        let actor_ref = LocalActorRef {
+            id: id.clone(),
+            sender,
+        };
+        let mut ctx = ActorContext::new(id, actor_ref.clone(), self.pubsub_registry.clone());
+
+        actor.started(&mut ctx).await;
+
+        tokio::spawn(async move {
+            while let Some(envelope) = receiver.recv().await {
+                envelope.handle(&mut actor, &mut ctx).await;
+            }
+            actor.stopped(&mut ctx).await;
+        });
+
+        Ok(actor_ref)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod net;
+pub mod stream;
+pub mod system;
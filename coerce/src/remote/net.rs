@@ -0,0 +1,54 @@
+pub use coerce_macros::StreamMessage;
+
+/// A message that can be carried over a distributed stream (see
+/// `coerce::remote::stream::pubsub`).
+///
+/// Most payloads don't need to implement this by hand - derive it instead:
+///
+/// ```ignore
+/// #[derive(Serialize, Deserialize, StreamMessage)]
+/// pub struct OrderPlaced {
+///     pub order_id: String,
+/// }
+/// ```
+///
+/// The derived impl encodes through the same JSON encoder used for remote
+/// actor messages (see [`crate::actor::message::encoding::json::RemoteMessage`]),
+/// so it gets `Serialize`/`DeserializeOwned` for free instead of hand-rolled
+/// byte matching.
+///
+/// Add `#[stream_message(versioned)]` to prefix the encoded bytes with a
+/// version byte (defaulting to [`DEFAULT_STREAM_MESSAGE_ENVELOPE_VERSION`]),
+/// or `#[stream_message(versioned = 2)]` to pick the version explicitly when
+/// a breaking change is made to the payload's wire format - `read_from_bytes`
+/// then rejects an encoding written by a different version of the type while
+/// a cluster is mid-rollout.
+pub trait StreamMessage: Sized {
+    fn read_from_bytes(data: Vec<u8>) -> Option<Self>;
+
+    fn write_to_bytes(&self) -> Option<Vec<u8>>;
+}
+
+/// Version used by `#[stream_message(versioned)]` when no explicit version
+/// is given.
+pub const DEFAULT_STREAM_MESSAGE_ENVELOPE_VERSION: u8 = 1;
+
+/// Helper used by the generated `StreamMessage` impl for versioned payloads.
+/// Prefixes the JSON-encoded body with `version` so `read_from_bytes` can
+/// reject (or, in the future, migrate) encodings written by a different
+/// version of the type while a cluster is rolling out.
+pub fn write_versioned_envelope(version: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(body.len() + 1);
+    bytes.push(version);
+    bytes.extend(body);
+    bytes
+}
+
+/// Inverse of [`write_versioned_envelope`]. Returns `None` if the envelope's
+/// version byte doesn't match `version`.
+pub fn read_versioned_envelope(version: u8, data: Vec<u8>) -> Option<Vec<u8>> {
+    match data.split_first() {
+        Some((&found, body)) if found == version => Some(body.to_vec()),
+        _ => None,
+    }
+}
@@ -0,0 +1,3 @@
+pub mod pubsub;
+
+pub use pubsub::{PubSub, PubSubError, StreamEvent, Topic};
@@ -0,0 +1,486 @@
+use crate::actor::context::ActorContext;
+use crate::actor::message::Handler;
+use crate::actor::{Actor, ActorId};
+use crate::remote::net::StreamMessage;
+use crate::remote::system::RemoteActorSystem;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::{broadcast, Notify, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+/// A named stream of events that actors can subscribe to.
+///
+/// Implementors declare the message type that subscribers receive via
+/// [`StreamEvent::Receive`] and a stable name used to address the topic
+/// across the cluster.
+pub trait Topic: 'static + Send + Sync {
+    type Message: 'static + Send + Sync;
+
+    /// Number of partitions this topic's events are sharded across. Topics
+    /// that don't override this have a single, unpartitioned stream - every
+    /// event is delivered to every subscriber, as before.
+    const PARTITIONS: u32 = 1;
+
+    fn topic_name() -> &'static str;
+
+    /// Extracts the routing key used to pick a partition for `msg`, so that
+    /// events sharing a key are delivered, in order, to the same partition.
+    /// Messages with no key (the default) are always routed to partition
+    /// `0`.
+    fn partition_key(_msg: &Self::Message) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// 64-bit FNV-1a, used to hash a [`Topic::partition_key`] into a partition
+/// index. Also reused by `remote::system` to pick a partition's owning node.
+pub(crate) fn fnv1a64(bytes: &[u8]) -> u64 {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes
+        .iter()
+        .fold(OFFSET, |hash, byte| (hash ^ *byte as u64).wrapping_mul(PRIME))
+}
+
+/// Picks the partition a message with the given key is routed to.
+fn partition_for(key: Option<&[u8]>, partitions: u32) -> u32 {
+    match key {
+        Some(key) if partitions > 1 => (fnv1a64(key) % partitions as u64) as u32,
+        _ => 0,
+    }
+}
+
+/// Why a [`StreamEvent::Err`] was raised.
+#[derive(Debug, Clone, Copy)]
+pub enum StreamErr {
+    /// The subscriber's reader task fell behind the shared broadcast channel
+    /// and `n` events were dropped from under it before it could catch up.
+    Lagged(u64),
+}
+
+/// An event delivered to an actor subscribed to topic `T`.
+pub enum StreamEvent<T: Topic> {
+    Receive(T::Message),
+    Err(StreamErr),
+}
+
+/// Errors returned by [`PubSub`] subscription operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PubSubError {
+    /// returned by [`PubSub::subscribe`] when the calling actor already holds
+    /// a subscription for the topic
+    AlreadySubscribed,
+
+    /// returned by [`PubSub::unsubscribe`] when the calling actor has no
+    /// subscription for the topic
+    NotSubscribed,
+}
+
+/// Size of the ring buffer backing each topic's broadcast channel. A reader
+/// task that falls this far behind the publisher gets a `StreamErr::Lagged`
+/// instead of the events it missed.
+const BROADCAST_CAPACITY: usize = 1024;
+
+/// What actually goes over a topic's shared broadcast channel: the message
+/// plus the partition it was routed to, so each subscriber's reader task can
+/// apply its own partition filter.
+struct BroadcastEnvelope<T: Topic> {
+    partition: u32,
+    message: T::Message,
+}
+
+// Can't derive `Clone` here: the derive would require `T: Clone`, but only
+// `T::Message` needs to be.
+impl<T: Topic> Clone for BroadcastEnvelope<T>
+where
+    T::Message: Clone,
+{
+    fn clone(&self) -> Self {
+        BroadcastEnvelope {
+            partition: self.partition,
+            message: self.message.clone(),
+        }
+    }
+}
+
+type TopicChannel<T> = broadcast::Sender<BroadcastEnvelope<T>>;
+
+/// A [`PubSub::subscribe_filtered`] predicate, boxed so `subscribe_internal`
+/// can take one optionally alongside the un-filtered subscribe paths.
+type SubscribePredicate<T> = Box<dyn Fn(&<T as Topic>::Message) -> bool + Send + Sync>;
+
+/// Tracks, per topic, the shared broadcast channel local subscribers read
+/// from, and, per actor, the set of topics (and the reader task) it
+/// currently holds a subscription to.
+///
+/// Owned by a single [`crate::actor::system::ActorSystem`] (reachable from
+/// [`ActorContext`] on the subscribe side and from a
+/// [`crate::remote::system::RemoteActorSystem`] on the publish side), so two
+/// separate systems - e.g. two simulated cluster nodes in the same process -
+/// never see each other's local subscribers.
+///
+/// The channel is type-erased (`Box<dyn Any>`) since a single map has to
+/// hold [`TopicChannel<T>`] for many different `T`; each access downcasts
+/// back to the concrete type for that topic.
+#[derive(Default)]
+pub(crate) struct Registry {
+    channels: HashMap<&'static str, Box<dyn Any + Send + Sync>>,
+    subscriptions: HashMap<ActorId, Vec<&'static str>>,
+    readers: HashMap<(ActorId, &'static str), JoinHandle<()>>,
+}
+
+impl Registry {
+    fn channel<T: Topic>(&mut self) -> TopicChannel<T>
+    where
+        T::Message: Clone,
+    {
+        self.channels
+            .entry(T::topic_name())
+            .or_insert_with(|| Box::new(broadcast::channel::<BroadcastEnvelope<T>>(BROADCAST_CAPACITY).0))
+            .downcast_ref::<TopicChannel<T>>()
+            .expect("topic registered with a mismatched message type")
+            .clone()
+    }
+}
+
+/// Entry point for subscribing to and publishing on distributed streams.
+///
+/// Local delivery is a single shared broadcast channel per topic: a publish
+/// writes to it exactly once, and every subscribed actor drives its own
+/// lightweight reader task that pulls from the channel and translates each
+/// item into a `Handler<StreamEvent<T>>` call. This keeps per-publish cost
+/// constant in the number of local subscribers, rather than the old
+/// one-send-per-subscriber fan-out.
+pub struct PubSub;
+
+impl PubSub {
+    /// Subscribes `ctx`'s actor to `topic`, delivering every subsequent
+    /// [`StreamEvent::Receive`] to it via the actor's `Handler<StreamEvent<T>>`
+    /// implementation.
+    ///
+    /// Returns `Err(PubSubError::AlreadySubscribed)` if the actor already
+    /// holds a subscription for this topic, which makes it safe to call
+    /// defensively (e.g. from `Actor::started`) without first checking
+    /// [`PubSub::is_subscribed`].
+    pub async fn subscribe<A, T>(topic: T, ctx: &mut ActorContext) -> Result<(), PubSubError>
+    where
+        A: Actor + Handler<StreamEvent<T>>,
+        T: Topic,
+        T::Message: Clone,
+    {
+        let _ = topic;
+        Self::subscribe_to_partitions::<A, T>(None, ctx).await
+    }
+
+    /// Like [`PubSub::subscribe`], but only delivers events routed to one of
+    /// `partitions`. Passing `None` subscribes to every partition, matching
+    /// [`PubSub::subscribe`]'s behavior.
+    pub async fn subscribe_to_partitions<A, T>(
+        partitions: Option<HashSet<u32>>,
+        ctx: &mut ActorContext,
+    ) -> Result<(), PubSubError>
+    where
+        A: Actor + Handler<StreamEvent<T>>,
+        T: Topic,
+        T::Message: Clone,
+    {
+        Self::subscribe_internal::<A, T>(partitions, None, ctx).await
+    }
+
+    /// Like [`PubSub::subscribe`], but events for which `predicate` returns
+    /// `false` are dropped by the reader task before the actor is ever
+    /// notified - they don't become a `StreamEvent::Receive` and never
+    /// schedule the actor. Because each subscriber's reader task runs on the
+    /// node the subscriber itself lives on, this filtering happens at the
+    /// point of local fan-out on that node, regardless of which node a
+    /// message was originally published from.
+    pub async fn subscribe_filtered<A, T>(
+        topic: T,
+        predicate: impl Fn(&T::Message) -> bool + Send + Sync + 'static,
+        ctx: &mut ActorContext,
+    ) -> Result<(), PubSubError>
+    where
+        A: Actor + Handler<StreamEvent<T>>,
+        T: Topic,
+        T::Message: Clone,
+    {
+        let _ = topic;
+        Self::subscribe_internal::<A, T>(None, Some(Box::new(predicate)), ctx).await
+    }
+
+    async fn subscribe_internal<A, T>(
+        partitions: Option<HashSet<u32>>,
+        predicate: Option<SubscribePredicate<T>>,
+        ctx: &mut ActorContext,
+    ) -> Result<(), PubSubError>
+    where
+        A: Actor + Handler<StreamEvent<T>>,
+        T: Topic,
+        T::Message: Clone,
+    {
+        let actor_id = ctx.id().clone();
+        let actor_ref = ctx.actor_ref::<A>();
+
+        let mut registry = ctx.pubsub_registry.write().await;
+        let subscribed_topics = registry.subscriptions.entry(actor_id.clone()).or_default();
+        if subscribed_topics.contains(&T::topic_name()) {
+            return Err(PubSubError::AlreadySubscribed);
+        }
+        subscribed_topics.push(T::topic_name());
+
+        let mut receiver = registry.channel::<T>().subscribe();
+        let reader = tokio::spawn(async move {
+            loop {
+                let event = match receiver.recv().await {
+                    Ok(envelope) => {
+                        if matches!(&partitions, Some(partitions) if !partitions.contains(&envelope.partition)) {
+                            continue;
+                        }
+                        if matches!(&predicate, Some(predicate) if !predicate(&envelope.message)) {
+                            continue;
+                        }
+                        StreamEvent::Receive(envelope.message)
+                    }
+                    Err(broadcast::error::RecvError::Lagged(n)) => StreamEvent::Err(StreamErr::Lagged(n)),
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                if actor_ref.notify(event).is_err() {
+                    break;
+                }
+            }
+        });
+        registry.readers.insert((actor_id, T::topic_name()), reader);
+
+        Ok(())
+    }
+
+    /// Removes `ctx`'s actor's subscription to topic `T`, if one exists,
+    /// stopping its reader task.
+    pub async fn unsubscribe<A, T>(ctx: &mut ActorContext) -> Result<(), PubSubError>
+    where
+        A: Actor + Handler<StreamEvent<T>>,
+        T: Topic,
+    {
+        let actor_id = ctx.id().clone();
+        let mut registry = ctx.pubsub_registry.write().await;
+
+        let removed = match registry.subscriptions.get_mut(&actor_id) {
+            Some(topics) => {
+                let before = topics.len();
+                topics.retain(|t| *t != T::topic_name());
+                before != topics.len()
+            }
+            None => false,
+        };
+
+        if !removed {
+            return Err(PubSubError::NotSubscribed);
+        }
+
+        if let Some(reader) = registry.readers.remove(&(actor_id, T::topic_name())) {
+            reader.abort();
+        }
+
+        Ok(())
+    }
+
+    /// Returns the names of every topic `ctx`'s actor currently holds a
+    /// subscription to.
+    pub async fn subscribed_topics(ctx: &mut ActorContext) -> Vec<&'static str> {
+        ctx.pubsub_registry
+            .read()
+            .await
+            .subscriptions
+            .get(ctx.id())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Returns `true` if `ctx`'s actor currently holds a subscription to
+    /// topic `T`.
+    pub async fn is_subscribed<T: Topic>(ctx: &mut ActorContext) -> bool {
+        ctx.pubsub_registry
+            .read()
+            .await
+            .subscriptions
+            .get(ctx.id())
+            .map(|topics| topics.contains(&T::topic_name()))
+            .unwrap_or(false)
+    }
+
+    /// Publishes `message` on `topic`.
+    ///
+    /// Unpartitioned topics (`T::PARTITIONS == 1`, the default) are
+    /// broadcast to every subscriber on every node in the cluster, matching
+    /// the behavior of a single, process-wide stream. Partitioned topics
+    /// instead route the event to the single cluster node that owns the
+    /// partition [`Topic::partition_key`] picks (via `sys`'s existing
+    /// cluster membership table), which then writes the event once to its
+    /// local subscribers' shared broadcast channel for the topic.
+    pub async fn publish<T: Topic>(_topic: T, message: T::Message, sys: &RemoteActorSystem)
+    where
+        T::Message: Clone,
+    {
+        let partition = partition_for(T::partition_key(&message).as_deref(), T::PARTITIONS);
+
+        if T::PARTITIONS > 1 {
+            if sys.node_for_key(T::topic_name(), partition).await != sys.node_id() {
+                sys.forward_stream_event::<T>(T::topic_name(), partition, message)
+                    .await;
+                return;
+            }
+        } else {
+            sys.forward_stream_event_to_all::<T>(T::topic_name(), partition, message.clone())
+                .await;
+        }
+
+        Self::deliver_local::<T>(sys, partition, message).await;
+    }
+
+    /// Like [`PubSub::publish`], but resolves only once the message has been
+    /// handed to every local subscriber and ACKed by every peer node this
+    /// node currently knows about, whether or not that peer has subscribers
+    /// for `T`'s topic - or `ack_timeout` elapses,
+    /// whichever comes first. The returned [`DeliveryReport`] reflects
+    /// whatever was actually delivered by then, so a caller can distinguish
+    /// "delivered" from "timed out with a partial fan-out".
+    ///
+    /// Respects partitioning exactly like [`PubSub::publish`]: a partitioned
+    /// topic is ACKed only by the single node that owns the partition, with
+    /// no local delivery on a node that isn't that owner.
+    pub async fn publish_acked<T: Topic>(
+        _topic: T,
+        message: T::Message,
+        sys: &RemoteActorSystem,
+        ack_timeout: Duration,
+    ) -> DeliveryReport
+    where
+        T::Message: Clone + StreamMessage,
+    {
+        let partition = partition_for(T::partition_key(&message).as_deref(), T::PARTITIONS);
+
+        let (deliver_locally, peers) = if T::PARTITIONS > 1 {
+            let owner = sys.node_for_key(T::topic_name(), partition).await;
+            if owner == sys.node_id() {
+                (true, Vec::new())
+            } else {
+                (false, vec![owner])
+            }
+        } else {
+            (true, sys.peers_subscribed_to(T::topic_name()).await)
+        };
+
+        let local_delivered = if deliver_locally {
+            Self::deliver_local::<T>(sys, partition, message.clone()).await
+        } else {
+            0
+        };
+
+        let expected = local_delivered + peers.len();
+
+        let outstanding = Arc::new(AtomicUsize::new(peers.len()));
+        let acked = Arc::new(Notify::new());
+        let bytes = message.write_to_bytes();
+
+        for peer in peers {
+            let outstanding = outstanding.clone();
+            let acked = acked.clone();
+            let sys = sys.clone();
+            let bytes = bytes.clone();
+
+            tokio::spawn(async move {
+                if let Some(bytes) = bytes {
+                    sys.forward_stream_event_acked::<T>(peer, partition, bytes)
+                        .await;
+                }
+
+                outstanding.fetch_sub(1, Ordering::SeqCst);
+                acked.notify_one();
+            });
+        }
+
+        let remote_acked = tokio::time::timeout(ack_timeout, async {
+            while outstanding.load(Ordering::SeqCst) > 0 {
+                acked.notified().await;
+            }
+        })
+        .await
+        .is_ok();
+
+        let remote_delivered = if remote_acked {
+            expected - local_delivered
+        } else {
+            (expected - local_delivered) - outstanding.load(Ordering::SeqCst)
+        };
+
+        DeliveryReport {
+            expected,
+            delivered: local_delivered + remote_delivered,
+        }
+    }
+
+    /// Writes `message` once to `T`'s shared local broadcast channel on
+    /// `sys`'s node. Returns the number of subscribers' reader tasks active
+    /// at the time of the write, which is what [`DeliveryReport`] reports as
+    /// "delivered" for the local side of a [`PubSub::publish_acked`] call.
+    async fn deliver_local<T: Topic>(sys: &RemoteActorSystem, partition: u32, message: T::Message) -> usize
+    where
+        T::Message: Clone,
+    {
+        deliver_to_registry::<T>(&sys.actor_system().pubsub_registry(), partition, message).await
+    }
+}
+
+/// Writes `message` once to `T`'s shared broadcast channel in `registry`.
+/// Returns the number of subscribers' reader tasks active at the time of the
+/// write. Shared by [`PubSub`]'s own local delivery and by
+/// `RemoteActorSystem::forward_stream_event*`, which deliver into a *peer*
+/// node's registry rather than the publishing node's own.
+pub(crate) async fn deliver_to_registry<T: Topic>(
+    registry: &Arc<RwLock<Registry>>,
+    partition: u32,
+    message: T::Message,
+) -> usize
+where
+    T::Message: Clone,
+{
+    let channel = registry.write().await.channel::<T>();
+    let receiver_count = channel.receiver_count();
+
+    // an error here just means there were no receivers to deliver to
+    let _ = channel.send(BroadcastEnvelope { partition, message });
+
+    // Each subscriber's reader task only picks this up once it's next
+    // scheduled, so without yielding here a caller that follows `publish`
+    // with something else routed through the same actor's mailbox (e.g.
+    // `LocalActorRef::exec`) could overtake this event. Yielding gives those
+    // reader tasks a turn to drain the channel and forward into their
+    // actor's mailbox first, so `publish`'s completion preserves delivery
+    // order relative to whatever the caller does next.
+    tokio::task::yield_now().await;
+
+    receiver_count
+}
+
+/// Outcome of a [`PubSub::publish_acked`] call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeliveryReport {
+    /// Local subscribers handed the message, plus peer nodes it was
+    /// forwarded to.
+    pub expected: usize,
+
+    /// How many of `expected` actually completed before the ack timeout.
+    pub delivered: usize,
+}
+
+impl DeliveryReport {
+    /// `true` if every expected delivery completed before the timeout.
+    pub fn is_complete(&self) -> bool {
+        self.delivered >= self.expected
+    }
+}
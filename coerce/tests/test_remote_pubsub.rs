@@ -1,11 +1,9 @@
 use coerce::actor::context::ActorContext;
-use coerce::actor::lifecycle::Status;
-use coerce::actor::message::encoding::json::RemoteMessage;
-use coerce::actor::message::{Handler, Message};
+use coerce::actor::message::Handler;
 use coerce::actor::system::ActorSystem;
-use coerce::actor::{new_actor, Actor};
+use coerce::actor::Actor;
 use coerce::remote::net::StreamMessage;
-use coerce::remote::stream::pubsub::{PubSub, StreamEvent, Topic};
+use coerce::remote::stream::pubsub::{PubSub, PubSubError, StreamEvent, Topic};
 use coerce::remote::system::RemoteActorSystem;
 use tokio::time::Duration;
 
@@ -17,7 +15,8 @@ extern crate serde;
 #[macro_use]
 extern crate async_trait;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize, StreamMessage)]
+#[stream_message(versioned)]
 pub enum StatusEvent {
     Online,
     Offline,
@@ -35,6 +34,8 @@ impl Topic for StatusStream {
 
 pub struct TestStreamConsumer {
     received_stream_messages: i32,
+    subscribed_topics: Vec<&'static str>,
+    resubscribe_result: Option<Result<(), PubSubError>>,
 }
 
 #[async_trait]
@@ -42,7 +43,14 @@ impl Actor for TestStreamConsumer {
     async fn started(&mut self, ctx: &mut ActorContext) {
         PubSub::subscribe::<Self, StatusStream>(StatusStream, ctx)
             .await
-            .unwrap()
+            .unwrap();
+
+        // safe to call defensively - subscribing a second time for the same
+        // topic must not register a duplicate subscription
+        self.resubscribe_result =
+            Some(PubSub::subscribe::<Self, StatusStream>(StatusStream, ctx).await);
+
+        self.subscribed_topics = PubSub::subscribed_topics(ctx).await;
     }
 
     async fn stopped(&mut self, ctx: &mut ActorContext) {
@@ -54,14 +62,14 @@ impl Actor for TestStreamConsumer {
 
 #[async_trait]
 impl Handler<StreamEvent<StatusStream>> for TestStreamConsumer {
-    async fn handle(&mut self, message: StreamEvent<StatusStream>, ctx: &mut ActorContext) {
+    async fn handle(&mut self, message: StreamEvent<StatusStream>, _ctx: &mut ActorContext) {
         match message {
             StreamEvent::Receive(msg) => {
                 log::info!("received msg: {:?}", &msg);
 
                 self.received_stream_messages += 1;
             }
-            StreamEvent::Err => {}
+            StreamEvent::Err(_) => {}
         }
     }
 }
@@ -70,25 +78,28 @@ impl Handler<StreamEvent<StatusStream>> for TestStreamConsumer {
 pub async fn test_pubsub_local() {
     util::create_trace_logger();
 
-    let mut sys = ActorSystem::new();
-    let mut remote = RemoteActorSystem::builder()
+    let sys = ActorSystem::new();
+    let remote = RemoteActorSystem::builder()
         .with_actor_system(sys)
-        .with_distributed_streams(|s| s.add_topic::<StatusStream>())
         .build()
         .await;
 
-    let mut actor = remote
+    let actor = remote
         .inner()
         .new_anon_actor(TestStreamConsumer {
             received_stream_messages: 0,
+            subscribed_topics: Vec::new(),
+            resubscribe_result: None,
         })
         .await
         .unwrap();
 
-    let mut actor_2 = remote
+    let actor_2 = remote
         .inner()
         .new_anon_actor(TestStreamConsumer {
             received_stream_messages: 0,
+            subscribed_topics: Vec::new(),
+            resubscribe_result: None,
         })
         .await
         .unwrap();
@@ -104,22 +115,348 @@ pub async fn test_pubsub_local() {
     assert_eq!(received_stream_messages_2, 10);
 }
 
+#[tokio::test]
+pub async fn test_pubsub_subscription_introspection() {
+    util::create_trace_logger();
+
+    let sys = ActorSystem::new();
+    let remote = RemoteActorSystem::builder()
+        .with_actor_system(sys)
+        .build()
+        .await;
+
+    let actor = remote
+        .inner()
+        .new_anon_actor(TestStreamConsumer {
+            received_stream_messages: 0,
+            subscribed_topics: Vec::new(),
+            resubscribe_result: None,
+        })
+        .await
+        .unwrap();
+
+    let subscribed_topics = actor.exec(|a| a.subscribed_topics.clone()).await.unwrap();
+    let resubscribe_result = actor.exec(|a| a.resubscribe_result.clone()).await.unwrap();
+
+    assert_eq!(subscribed_topics, vec![StatusStream::topic_name()]);
+    assert_eq!(resubscribe_result, Some(Err(PubSubError::AlreadySubscribed)));
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, StreamMessage)]
+pub struct OrderPlaced {
+    order_id: u32,
+}
+
+pub struct OrderStream;
+
+impl Topic for OrderStream {
+    type Message = OrderPlaced;
+
+    const PARTITIONS: u32 = 4;
+
+    fn topic_name() -> &'static str {
+        "order-stream"
+    }
+
+    fn partition_key(msg: &Self::Message) -> Option<Vec<u8>> {
+        Some(msg.order_id.to_be_bytes().to_vec())
+    }
+}
+
+pub struct PartitionConsumer {
+    received_order_ids: Vec<u32>,
+}
+
+#[async_trait]
+impl Actor for PartitionConsumer {
+    async fn started(&mut self, ctx: &mut ActorContext) {
+        let mut partitions = std::collections::HashSet::new();
+        partitions.insert(0);
+
+        PubSub::subscribe_to_partitions::<Self, OrderStream>(Some(partitions), ctx)
+            .await
+            .unwrap()
+    }
+
+    async fn stopped(&mut self, ctx: &mut ActorContext) {
+        PubSub::unsubscribe::<Self, OrderStream>(ctx).await.unwrap()
+    }
+}
+
+#[async_trait]
+impl Handler<StreamEvent<OrderStream>> for PartitionConsumer {
+    async fn handle(&mut self, message: StreamEvent<OrderStream>, _ctx: &mut ActorContext) {
+        if let StreamEvent::Receive(order) = message {
+            self.received_order_ids.push(order.order_id);
+        }
+    }
+}
+
+#[tokio::test]
+pub async fn test_pubsub_partitioned_topic_routes_by_key() {
+    util::create_trace_logger();
+
+    let sys = ActorSystem::new();
+    let remote = RemoteActorSystem::builder()
+        .with_actor_system(sys)
+        .build()
+        .await;
+
+    let actor = remote
+        .inner()
+        .new_anon_actor(PartitionConsumer {
+            received_order_ids: Vec::new(),
+        })
+        .await
+        .unwrap();
+
+    // order ids land in different partitions depending on their key; the
+    // consumer only subscribed to partition 0, so it should only ever see a
+    // subset of the orders published below.
+    for order_id in 0..20 {
+        PubSub::publish(OrderStream, OrderPlaced { order_id }, remote.inner()).await;
+    }
+
+    let received_order_ids = actor.exec(|a| a.received_order_ids.clone()).await.unwrap();
+
+    assert!(!received_order_ids.is_empty());
+    assert!(received_order_ids.len() < 20);
+}
+
+#[tokio::test]
+pub async fn test_pubsub_partitioned_topic_forwards_to_owning_node() {
+    util::create_trace_logger();
+
+    let sys = ActorSystem::new();
+    let remote = RemoteActorSystem::builder()
+        .with_actor_system(sys)
+        .build()
+        .await;
+
+    let sys = ActorSystem::new();
+    let remote_b = RemoteActorSystem::builder()
+        .with_actor_system(sys)
+        .build()
+        .await;
+
+    remote.clone()
+        .cluster_worker()
+        .listen_addr("localhost:30301")
+        .start()
+        .await;
+
+    remote_b.clone()
+        .cluster_worker()
+        .listen_addr("localhost:30302")
+        .with_seed_addr("localhost:30301")
+        .start()
+        .await;
+
+    // subscribed to every partition, on both nodes - proves a publish made
+    // on `remote` actually crosses the wire to `remote_b` for whichever
+    // partitions it owns, rather than only ever being delivered locally
+    let actor = remote
+        .inner()
+        .new_anon_actor(AllPartitionsConsumer {
+            received_order_ids: Vec::new(),
+        })
+        .await
+        .unwrap();
+
+    let actor_b = remote_b
+        .inner()
+        .new_anon_actor(AllPartitionsConsumer {
+            received_order_ids: Vec::new(),
+        })
+        .await
+        .unwrap();
+
+    // published entirely from `remote`; any order id whose partition is
+    // owned by `remote_b` must reach `remote_b`'s subscriber, not `remote`'s
+    for order_id in 0..20 {
+        PubSub::publish(OrderStream, OrderPlaced { order_id }, remote.inner()).await;
+    }
+
+    let received_order_ids = actor.exec(|a| a.received_order_ids.clone()).await.unwrap();
+    let received_order_ids_b = actor_b.exec(|a| a.received_order_ids.clone()).await.unwrap();
+
+    // every order id was delivered exactly once, split across the two nodes
+    // by partition ownership - the publishing node alone didn't see all of
+    // them, proving the non-owned ones genuinely crossed to `remote_b`
+    assert_eq!(received_order_ids.len() + received_order_ids_b.len(), 20);
+    assert!(!received_order_ids_b.is_empty());
+}
+
+pub struct AllPartitionsConsumer {
+    received_order_ids: Vec<u32>,
+}
+
+#[async_trait]
+impl Actor for AllPartitionsConsumer {
+    async fn started(&mut self, ctx: &mut ActorContext) {
+        PubSub::subscribe::<Self, OrderStream>(OrderStream, ctx)
+            .await
+            .unwrap()
+    }
+
+    async fn stopped(&mut self, ctx: &mut ActorContext) {
+        PubSub::unsubscribe::<Self, OrderStream>(ctx).await.unwrap()
+    }
+}
+
+#[async_trait]
+impl Handler<StreamEvent<OrderStream>> for AllPartitionsConsumer {
+    async fn handle(&mut self, message: StreamEvent<OrderStream>, _ctx: &mut ActorContext) {
+        if let StreamEvent::Receive(order) = message {
+            self.received_order_ids.push(order.order_id);
+        }
+    }
+}
+
+#[tokio::test]
+pub async fn test_pubsub_partitioned_publish_acked_across_nodes() {
+    util::create_trace_logger();
+
+    let sys = ActorSystem::new();
+    let remote = RemoteActorSystem::builder()
+        .with_actor_system(sys)
+        .build()
+        .await;
+
+    let sys = ActorSystem::new();
+    let remote_b = RemoteActorSystem::builder()
+        .with_actor_system(sys)
+        .build()
+        .await;
+
+    remote.clone()
+        .cluster_worker()
+        .listen_addr("localhost:30201")
+        .start()
+        .await;
+
+    remote_b.clone()
+        .cluster_worker()
+        .listen_addr("localhost:30202")
+        .with_seed_addr("localhost:30201")
+        .start()
+        .await;
+
+    // subscribed to every partition, on both nodes - each order id should
+    // only ever show up on whichever one owns its partition
+    let actor = remote
+        .inner()
+        .new_anon_actor(AllPartitionsConsumer {
+            received_order_ids: Vec::new(),
+        })
+        .await
+        .unwrap();
+
+    let actor_b = remote_b
+        .inner()
+        .new_anon_actor(AllPartitionsConsumer {
+            received_order_ids: Vec::new(),
+        })
+        .await
+        .unwrap();
+
+    // published entirely from `remote`; for partitions `remote_b` owns,
+    // publish_acked must forward the message and wait on `remote_b`'s ack
+    // rather than acking locally, matching PubSub::publish's routing
+    for order_id in 0..20 {
+        let report = PubSub::publish_acked(
+            OrderStream,
+            OrderPlaced { order_id },
+            remote.inner(),
+            Duration::from_secs(1),
+        )
+        .await;
+        assert!(report.is_complete());
+    }
+
+    let received_order_ids = actor.exec(|a| a.received_order_ids.clone()).await.unwrap();
+    let received_order_ids_b = actor_b.exec(|a| a.received_order_ids.clone()).await.unwrap();
+
+    // every order id was delivered exactly once, split across the two nodes
+    // by partition ownership - neither node alone saw all of them
+    assert_eq!(received_order_ids.len() + received_order_ids_b.len(), 20);
+    assert!(!received_order_ids.is_empty());
+    assert!(!received_order_ids_b.is_empty());
+}
+
+pub struct FilteredStreamConsumer {
+    received_offline_events: i32,
+}
+
+#[async_trait]
+impl Actor for FilteredStreamConsumer {
+    async fn started(&mut self, ctx: &mut ActorContext) {
+        PubSub::subscribe_filtered::<Self, StatusStream>(
+            StatusStream,
+            |event| matches!(event, StatusEvent::Offline),
+            ctx,
+        )
+        .await
+        .unwrap()
+    }
+
+    async fn stopped(&mut self, ctx: &mut ActorContext) {
+        PubSub::unsubscribe::<Self, StatusStream>(ctx).await.unwrap()
+    }
+}
+
+#[async_trait]
+impl Handler<StreamEvent<StatusStream>> for FilteredStreamConsumer {
+    async fn handle(&mut self, message: StreamEvent<StatusStream>, _ctx: &mut ActorContext) {
+        if let StreamEvent::Receive(StatusEvent::Offline) = message {
+            self.received_offline_events += 1;
+        }
+    }
+}
+
+#[tokio::test]
+pub async fn test_pubsub_subscribe_filtered_drops_events_before_dispatch() {
+    util::create_trace_logger();
+
+    let sys = ActorSystem::new();
+    let remote = RemoteActorSystem::builder()
+        .with_actor_system(sys)
+        .build()
+        .await;
+
+    let actor = remote
+        .inner()
+        .new_anon_actor(FilteredStreamConsumer {
+            received_offline_events: 0,
+        })
+        .await
+        .unwrap();
+
+    for _ in 0..10 {
+        PubSub::publish(StatusStream, StatusEvent::Online, remote.inner()).await;
+    }
+    for _ in 0..3 {
+        PubSub::publish(StatusStream, StatusEvent::Offline, remote.inner()).await;
+    }
+
+    let received_offline_events = actor.exec(|a| a.received_offline_events).await.unwrap();
+
+    assert_eq!(received_offline_events, 3);
+}
 
 #[tokio::test]
 pub async fn test_pubsub_distributed() {
     // util::create_trace_logger();
 
-    let mut sys = ActorSystem::new();
-    let mut remote = RemoteActorSystem::builder()
+    let sys = ActorSystem::new();
+    let remote = RemoteActorSystem::builder()
         .with_actor_system(sys)
-        .with_distributed_streams(|s| s.add_topic::<StatusStream>())
         .build()
         .await;
 
-    let mut sys = ActorSystem::new();
-    let mut remote_b = RemoteActorSystem::builder()
+    let sys = ActorSystem::new();
+    let remote_b = RemoteActorSystem::builder()
         .with_actor_system(sys)
-        .with_distributed_streams(|s| s.add_topic::<StatusStream>())
         .build()
         .await;
 
@@ -136,36 +473,47 @@ pub async fn test_pubsub_distributed() {
         .start()
         .await;
 
-    let mut actor = remote
+    let actor = remote
         .inner()
         .new_anon_actor(TestStreamConsumer {
             received_stream_messages: 0,
+            subscribed_topics: Vec::new(),
+            resubscribe_result: None,
         })
         .await
         .unwrap();
 
-    let mut actor_2 = remote_b
+    let actor_2 = remote_b
         .inner()
         .new_anon_actor(TestStreamConsumer {
             received_stream_messages: 0,
+            subscribed_topics: Vec::new(),
+            resubscribe_result: None,
         })
         .await
         .unwrap();
 
-    // Publish 5 messages on the first server
+    // Publish 5 messages on the first server, waiting for each to be handed
+    // to local subscribers and ACKed by every peer node before moving on
     for _ in 0..5 {
-        PubSub::publish(StatusStream, StatusEvent::Online, remote.inner()).await;
+        let report =
+            PubSub::publish_acked(StatusStream, StatusEvent::Online, remote.inner(), Duration::from_secs(1))
+                .await;
+        assert!(report.is_complete());
     }
 
     // Publish 5 messages on the second server
     for _ in 0..5 {
-        PubSub::publish(StatusStream, StatusEvent::Online, remote_b.inner()).await;
+        let report = PubSub::publish_acked(
+            StatusStream,
+            StatusEvent::Online,
+            remote_b.inner(),
+            Duration::from_secs(1),
+        )
+        .await;
+        assert!(report.is_complete());
     }
 
-    // remote stream publishing is asynchronous so there's no way to wait until actors have processed the messages
-    // todo: create oneshot channel within test actors that publish once the actor receives 10 messages so we don't need to delay here
-    tokio::time::sleep(Duration::from_millis(5)).await;
-
     // ensure both actors (one on each system) receives all stream messages from both servers
     let received_stream_messages = actor.exec(|a| a.received_stream_messages).await.unwrap();
     let received_stream_messages_2 = actor_2.exec(|a| a.received_stream_messages).await.unwrap();
@@ -174,20 +522,69 @@ pub async fn test_pubsub_distributed() {
     assert_eq!(received_stream_messages_2, 10);
 }
 
+#[tokio::test]
+pub async fn test_pubsub_distributed_plain_publish() {
+    // Unlike `test_pubsub_distributed`, this exercises plain `PubSub::publish`
+    // (not `publish_acked`) across two nodes, since an unpartitioned topic's
+    // events must reach every subscriber in the cluster regardless of which
+    // publish variant is used.
+    let sys = ActorSystem::new();
+    let remote = RemoteActorSystem::builder()
+        .with_actor_system(sys)
+        .build()
+        .await;
 
-impl StreamMessage for StatusEvent {
-    fn read_from_bytes(data: Vec<u8>) -> Option<Self> {
-        match data.first() {
-            Some(0) => Some(StatusEvent::Offline),
-            Some(1) => Some(StatusEvent::Online),
-            _ => None,
-        }
-    }
+    let sys = ActorSystem::new();
+    let remote_b = RemoteActorSystem::builder()
+        .with_actor_system(sys)
+        .build()
+        .await;
 
-    fn write_to_bytes(&self) -> Option<Vec<u8>> {
-        match &self {
-            StatusEvent::Offline => Some(vec![0]),
-            StatusEvent::Online => Some(vec![1]),
-        }
+    remote.clone()
+        .cluster_worker()
+        .listen_addr("localhost:30401")
+        .start()
+        .await;
+
+    remote_b.clone()
+        .cluster_worker()
+        .listen_addr("localhost:30402")
+        .with_seed_addr("localhost:30401")
+        .start()
+        .await;
+
+    let actor = remote
+        .inner()
+        .new_anon_actor(TestStreamConsumer {
+            received_stream_messages: 0,
+            subscribed_topics: Vec::new(),
+            resubscribe_result: None,
+        })
+        .await
+        .unwrap();
+
+    let actor_2 = remote_b
+        .inner()
+        .new_anon_actor(TestStreamConsumer {
+            received_stream_messages: 0,
+            subscribed_topics: Vec::new(),
+            resubscribe_result: None,
+        })
+        .await
+        .unwrap();
+
+    // Published from the first node, with no acking - both nodes' local
+    // subscribers must still see every event.
+    for _ in 0..5 {
+        PubSub::publish(StatusStream, StatusEvent::Online, remote.inner()).await;
     }
+
+    // give the cross-node forward a moment to land before asserting
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let received_stream_messages = actor.exec(|a| a.received_stream_messages).await.unwrap();
+    let received_stream_messages_2 = actor_2.exec(|a| a.received_stream_messages).await.unwrap();
+
+    assert_eq!(received_stream_messages, 5);
+    assert_eq!(received_stream_messages_2, 5);
 }
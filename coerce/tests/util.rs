@@ -0,0 +1,10 @@
+/// Installs a trace-level logger for the duration of the test process, if
+/// one hasn't already been installed. Safe to call from every test: the
+/// tests of a test binary share one process and `env_logger` only allows a
+/// single global logger to be set, so every call after the first is a no-op.
+pub fn create_trace_logger() {
+    let _ = env_logger::builder()
+        .is_test(true)
+        .filter_level(log::LevelFilter::Trace)
+        .try_init();
+}